@@ -1,21 +1,19 @@
-use anot;
-use anyhow::Ok;
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use std::path::PathBuf;
 
 fn benchmark_execution(c: &mut Criterion) {
-    c.bench_function("Benchmark results", |b| {
+    let paths = vec![PathBuf::from("./alacritty/")];
+    let tags = vec!["hypothesis".to_string(), "note".to_string(), "todo".to_string()];
+
+    c.bench_function("Benchmark results (parallel)", |b| {
+        b.iter(|| {
+            anot::cli::run_with_options(black_box(&paths), black_box(&tags), true, false, true, false)
+        })
+    });
+
+    c.bench_function("Benchmark results (single-threaded)", |b| {
         b.iter(|| {
-            let args: Vec<String> = black_box(
-                [
-                    "./target/release/anot".to_string(),
-                    "./alacritty/".to_string(),
-                    "--tags".to_string(),
-                    "hypothesis,note,todo".to_string(),
-                ]
-                .to_vec(),
-            );
-            anot::cli::run(args)?;
-            Ok(())
+            anot::cli::run_with_options(black_box(&paths), black_box(&tags), false, false, true, false)
         })
     });
 }
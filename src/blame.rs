@@ -0,0 +1,167 @@
+use crate::hunks::{normalize_path, GitDiffer};
+use std::path::Path;
+
+/// Attribution for a single source line: the commit that last touched it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineBlame {
+    pub commit_id: String,
+    pub author: String,
+    pub time: i64,
+}
+
+impl LineBlame {
+    /// Days elapsed between this line's commit and now, floored at zero.
+    /// Backs "todos older than N days"-style reporting.
+    pub fn age_days(&self) -> i64 {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(self.time);
+        ((now - self.time) / 86_400).max(0)
+    }
+}
+
+impl GitDiffer {
+    /// Blame `file_path`, returning attribution for every line (1-indexed,
+    /// so index 0 of the returned vector holds line 1's attribution).
+    ///
+    /// Returns `None` when the file is untracked or outside a repository,
+    /// letting callers fall back to line-number-only output instead of
+    /// erroring out.
+    pub fn blame_lines(&self, file_path: impl AsRef<Path>) -> Option<Vec<LineBlame>> {
+        let workdir = normalize_path(self.repo.workdir()?);
+        // Normalize the same way `GitDiffer::modified_line_numbers` does: a
+        // relative path (or one carrying a literal "./" prefix, as
+        // `scan_directory` produces) otherwise fails to strip against the
+        // workdir and this returns `None` for every realistic caller.
+        let file_path = normalize_path(file_path.as_ref());
+        let relative_path = file_path.strip_prefix(&workdir).ok()?;
+
+        let blame = self.repo.blame_file(relative_path, None).ok()?;
+        let mut lines = Vec::new();
+        for hunk in blame.iter() {
+            let commit_id = hunk.final_commit_id();
+            let commit = self.repo.find_commit(commit_id).ok()?;
+            let author = commit.author().name().unwrap_or("unknown").to_string();
+            let time = commit.time().seconds();
+
+            for _ in 0..hunk.lines_in_hunk() {
+                lines.push(LineBlame {
+                    commit_id: commit_id.to_string(),
+                    author: author.clone(),
+                    time,
+                });
+            }
+        }
+        Some(lines)
+    }
+}
+
+/// Join per-line blame attribution with a set of annotated line numbers.
+///
+/// `annotated_lines` are 1-indexed line numbers carrying a tag; the
+/// returned vector pairs each with its attribution, in ascending line
+/// order. Lines outside `blame`'s range (e.g. stale line numbers) are
+/// dropped rather than panicking.
+pub fn attribute_annotations(
+    blame: &[LineBlame],
+    annotated_lines: impl IntoIterator<Item = usize>,
+) -> Vec<(usize, LineBlame)> {
+    let mut result: Vec<(usize, LineBlame)> = annotated_lines
+        .into_iter()
+        .filter_map(|line| {
+            let attribution = blame.get(line.checked_sub(1)?)?.clone();
+            Some((line, attribution))
+        })
+        .collect();
+    result.sort_by_key(|(line, _)| *line);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use std::io::Write;
+    use std::path::PathBuf;
+    use std::process::Command;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_blame_lines() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+
+        Command::new("git").args(["init"]).current_dir(&dir).output()?;
+        Command::new("git")
+            .args(["config", "user.email", "test@example.com"])
+            .current_dir(&dir)
+            .output()?;
+        Command::new("git")
+            .args(["config", "user.name", "Test User"])
+            .current_dir(&dir)
+            .output()?;
+
+        let file_path = dir.path().join("test.txt");
+        let mut file = File::create(&file_path)?;
+        writeln!(file, "line 1\nline 2")?;
+
+        Command::new("git")
+            .args(["add", "test.txt"])
+            .current_dir(&dir)
+            .output()?;
+        Command::new("git")
+            .args(["commit", "-m", "initial"])
+            .current_dir(&dir)
+            .output()?;
+
+        let differ = GitDiffer::discover(&dir)?;
+        let blame = differ.blame_lines(&file_path).expect("file is tracked");
+
+        assert_eq!(blame.len(), 2);
+        assert_eq!(blame[0].author, "Test User");
+
+        let attributed = attribute_annotations(&blame, [1, 2]);
+        assert_eq!(attributed.len(), 2);
+        assert_eq!(attributed[0].0, 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_blame_lines_with_dot_slash_prefix() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+
+        Command::new("git").args(["init"]).current_dir(&dir).output()?;
+        Command::new("git")
+            .args(["config", "user.email", "test@example.com"])
+            .current_dir(&dir)
+            .output()?;
+        Command::new("git")
+            .args(["config", "user.name", "Test User"])
+            .current_dir(&dir)
+            .output()?;
+
+        let file_path = dir.path().join("test.txt");
+        let mut file = File::create(&file_path)?;
+        writeln!(file, "line 1\nline 2")?;
+
+        Command::new("git")
+            .args(["add", "test.txt"])
+            .current_dir(&dir)
+            .output()?;
+        Command::new("git")
+            .args(["commit", "-m", "initial"])
+            .current_dir(&dir)
+            .output()?;
+
+        let differ = GitDiffer::discover(&dir)?;
+
+        // A literal "./" prefix, exactly what `scan_directory(".")` yields,
+        // shouldn't make blame_lines return None.
+        let dotted_path = PathBuf::from(format!("{}/./test.txt", dir.path().display()));
+        let blame = differ.blame_lines(&dotted_path).expect("file is tracked");
+        assert_eq!(blame.len(), 2);
+
+        Ok(())
+    }
+}
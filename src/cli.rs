@@ -0,0 +1,334 @@
+use crate::blame::{attribute_annotations, LineBlame};
+use crate::hunks::GitDiffer;
+use crate::input::{
+    determine_file_type, language_spec, read_file, scan_directory_with_options, tree_sitter_query,
+};
+use anyhow::{Context, Result};
+use rayon::prelude::*;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+/// A single tagged comment found in a source file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Annotation {
+    pub path: PathBuf,
+    pub line: usize,
+    pub tag: String,
+    pub text: String,
+    /// Git-blame attribution for this line, populated when `with_blame` is
+    /// requested (see [`run_with_options`]); `None` otherwise, or when the
+    /// file is untracked or outside a repository.
+    pub blame: Option<LineBlame>,
+}
+
+thread_local! {
+    // `tree_sitter::Parser` is not `Sync`, so each rayon worker thread keeps
+    // its own, rebuilt only when the extension it last parsed changes.
+    static PARSER: RefCell<Option<(String, tree_sitter::Parser)>> = const { RefCell::new(None) };
+}
+
+/// Extract tagged annotations from a single file, reusing this thread's
+/// cached parser when its language matches the file being read.
+pub(crate) fn extract_file(path: &PathBuf, tags: &[String]) -> Result<Vec<Annotation>> {
+    let extension = determine_file_type(path)?;
+    let spec = language_spec(&extension).context("Language registry entry vanished")?;
+    let source = read_file(path)?;
+
+    PARSER.with(|cell| {
+        let mut cell = cell.borrow_mut();
+        let needs_new_parser = !matches!(&*cell, Some((ext, _)) if *ext == extension);
+        if needs_new_parser {
+            let mut parser = tree_sitter::Parser::new();
+            parser
+                .set_language(&spec.language)
+                .context("Failed to load tree-sitter grammar")?;
+            *cell = Some((extension.clone(), parser));
+        }
+        let (_, parser) = cell.as_mut().expect("parser was just initialized");
+
+        let tree = parser
+            .parse(&source, None)
+            .with_context(|| format!("Failed to parse {path:?}"))?;
+
+        let query = tree_sitter_query(&extension).context("Language registry entry vanished")?;
+        let mut cursor = tree_sitter::QueryCursor::new();
+        let mut annotations = Vec::new();
+        for m in cursor.matches(&query, tree.root_node(), source.as_bytes()) {
+            for capture in m.captures {
+                let comment = &source[capture.node.byte_range()];
+                if let Some((tag, text)) = match_tag(comment, tags) {
+                    annotations.push(Annotation {
+                        path: path.clone(),
+                        line: capture.node.start_position().row + 1,
+                        tag,
+                        text,
+                        blame: None,
+                    });
+                }
+            }
+        }
+        Ok(annotations)
+    })
+}
+
+/// Find the first tag (case-insensitive) present in a comment, returning it
+/// alongside the remaining comment text.
+fn match_tag(comment: &str, tags: &[String]) -> Option<(String, String)> {
+    let upper = comment.to_uppercase();
+    tags.iter().find_map(|tag| {
+        let idx = upper.find(&tag.to_uppercase())?;
+        let text = comment[idx + tag.len()..]
+            .trim_start_matches(':')
+            .trim()
+            .to_string();
+        Some((tag.clone(), text))
+    })
+}
+
+/// Scan `paths` and extract annotations carrying one of `tags`.
+///
+/// Files are processed in parallel via rayon; pass `parallel: false` to
+/// fall back to single-threaded extraction for reproducible benchmarking
+/// against the criterion harness. When `changed_only` is set, annotations
+/// are further restricted to modified or added lines (see
+/// [`filter_to_changed_lines`]). When `with_blame` is set, each annotation's
+/// [`Annotation::blame`] is populated with its line's git-blame attribution
+/// (see [`attribute_blame`]), enabling "todos older than N days" or
+/// "group by author" style post-processing. Both features share a single
+/// [`GitDiffer`] opened once and reused for every file. Pass
+/// `respect_ignore: false` to walk every file regardless of
+/// `.gitignore`/`.ignore` rules (see
+/// [`crate::input::scan_directory_with_options`]). Output is always sorted
+/// by path and line so it's deterministic regardless of thread scheduling.
+pub fn run_with_options(
+    paths: &[PathBuf],
+    tags: &[String],
+    parallel: bool,
+    changed_only: bool,
+    respect_ignore: bool,
+    with_blame: bool,
+) -> Result<Vec<Annotation>> {
+    let mut files = Vec::new();
+    for path in paths {
+        files.extend(scan_directory_with_options(path, respect_ignore)?);
+    }
+
+    let mut annotations: Vec<Annotation> = if parallel {
+        files
+            .par_iter()
+            .filter_map(|file| extract_file(file, tags).ok())
+            .flatten()
+            .collect()
+    } else {
+        files
+            .iter()
+            .filter_map(|file| extract_file(file, tags).ok())
+            .flatten()
+            .collect()
+    };
+
+    if changed_only || with_blame {
+        let discover_from = paths.first().cloned().unwrap_or_else(|| PathBuf::from("."));
+        let differ = GitDiffer::discover(discover_from).ok();
+        if changed_only {
+            annotations = filter_to_changed_lines(annotations, differ.as_ref());
+        }
+        if with_blame {
+            annotations = attribute_blame(annotations, differ.as_ref());
+        }
+    }
+
+    annotations.sort_by(|a, b| a.path.cmp(&b.path).then(a.line.cmp(&b.line)));
+    Ok(annotations)
+}
+
+/// Keep only annotations that fall on a modified or added line.
+///
+/// `differ` is opened once by the caller and reused here for every
+/// annotation; lines are additionally cached per file path, so a file with
+/// several tagged comments has its diff computed only once rather than
+/// once per annotation. Returns no annotations if `differ` is `None` (e.g.
+/// the scanned paths aren't inside a git repository).
+pub(crate) fn filter_to_changed_lines(
+    annotations: Vec<Annotation>,
+    differ: Option<&GitDiffer>,
+) -> Vec<Annotation> {
+    let Some(differ) = differ else {
+        return Vec::new();
+    };
+
+    let mut modified_cache: HashMap<PathBuf, HashSet<usize>> = HashMap::new();
+    annotations
+        .into_iter()
+        .filter(|annotation| {
+            let modified = modified_cache.entry(annotation.path.clone()).or_insert_with(|| {
+                differ
+                    .modified_line_numbers(&annotation.path, None)
+                    .unwrap_or_default()
+            });
+            modified.contains(&annotation.line)
+        })
+        .collect()
+}
+
+/// Attach git-blame attribution to each annotation.
+///
+/// `differ` is opened once by the caller and reused here; each distinct
+/// file is blamed only once regardless of how many tagged comments it
+/// holds, via [`GitDiffer::blame_lines`] plus [`attribute_annotations`].
+/// Annotations in untracked files or outside a repository keep
+/// `blame: None`. Returns `annotations` unchanged if `differ` is `None`.
+pub(crate) fn attribute_blame(
+    annotations: Vec<Annotation>,
+    differ: Option<&GitDiffer>,
+) -> Vec<Annotation> {
+    let Some(differ) = differ else {
+        return annotations;
+    };
+
+    let mut lines_by_file: HashMap<PathBuf, Vec<usize>> = HashMap::new();
+    for annotation in &annotations {
+        lines_by_file
+            .entry(annotation.path.clone())
+            .or_default()
+            .push(annotation.line);
+    }
+
+    let mut lookup: HashMap<(PathBuf, usize), LineBlame> = HashMap::new();
+    for (path, lines) in lines_by_file {
+        if let Some(blame) = differ.blame_lines(&path) {
+            for (line, attribution) in attribute_annotations(&blame, lines) {
+                lookup.insert((path.clone(), line), attribution);
+            }
+        }
+    }
+
+    annotations
+        .into_iter()
+        .map(|mut annotation| {
+            annotation.blame = lookup.get(&(annotation.path.clone(), annotation.line)).cloned();
+            annotation
+        })
+        .collect()
+}
+
+/// Entry point shared by the binary and the criterion benchmark.
+///
+/// `args` mirrors `std::env::args()`: `args[0]` is the program name,
+/// followed by one or more root paths to scan, an optional `--tags` flag
+/// taking a comma-separated list of tags (defaults to
+/// `hypothesis,note,todo`), an optional `--changed-only` flag to restrict
+/// output to modified lines, an optional `--no-ignore` flag to walk every
+/// file regardless of `.gitignore`/`.ignore` rules, an optional `--blame`
+/// flag to annotate each line with its git author and age, an optional
+/// `--no-parallel` flag to fall back to single-threaded extraction (useful
+/// for reproducible benchmarking against the criterion harness), an
+/// optional `--watch` flag to keep re-scanning and reprinting as files
+/// change instead of exiting after one pass, and an optional
+/// `--html <path>` flag to additionally write a syntax-highlighted HTML
+/// report (see [`crate::html::render`]).
+pub fn run(args: Vec<String>) -> Result<()> {
+    let mut paths = Vec::new();
+    let mut tags = vec!["hypothesis".to_string(), "note".to_string(), "todo".to_string()];
+    let mut changed_only = false;
+    let mut respect_ignore = true;
+    let mut with_blame = false;
+    let mut parallel = true;
+    let mut do_watch = false;
+    let mut html_path = None;
+
+    let mut iter = args.into_iter().skip(1);
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--tags" => {
+                if let Some(value) = iter.next() {
+                    tags = value.split(',').map(str::to_string).collect();
+                }
+            }
+            "--changed-only" => changed_only = true,
+            "--no-ignore" => respect_ignore = false,
+            "--blame" => with_blame = true,
+            "--no-parallel" => parallel = false,
+            "--watch" => do_watch = true,
+            "--html" => html_path = iter.next().map(PathBuf::from),
+            _ => paths.push(PathBuf::from(arg)),
+        }
+    }
+
+    if do_watch {
+        return crate::watch::watch(&paths, &tags, changed_only, with_blame, respect_ignore);
+    }
+
+    let annotations = run_with_options(&paths, &tags, parallel, changed_only, respect_ignore, with_blame)?;
+
+    for annotation in &annotations {
+        let attribution = annotation
+            .blame
+            .as_ref()
+            .map(|b| format!(" ({}, {}d old)", b.author, b.age_days()))
+            .unwrap_or_default();
+        println!(
+            "{}:{}: [{}] {}{}",
+            annotation.path.display(),
+            annotation.line,
+            annotation.tag,
+            annotation.text,
+            attribution
+        );
+    }
+
+    if let Some(html_path) = html_path {
+        let report = crate::html::render(&annotations)?;
+        std::fs::write(&html_path, report)
+            .with_context(|| format!("Failed to write HTML report to {html_path:?}"))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_run_with_options_finds_tagged_comments() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(
+            dir.path().join("lib.rs"),
+            "// TODO: fix this\nfn main() {}\n",
+        )?;
+
+        let tags = vec!["TODO".to_string()];
+        let found = run_with_options(&[dir.path().to_path_buf()], &tags, false, false, true, false)?;
+
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].line, 1);
+        assert_eq!(found[0].tag, "TODO");
+        assert_eq!(found[0].text, "fix this");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parallel_and_serial_extraction_agree() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        for i in 0..5 {
+            fs::write(
+                dir.path().join(format!("file{i}.rs")),
+                format!("// NOTE: item {i}\n"),
+            )?;
+        }
+
+        let tags = vec!["NOTE".to_string()];
+        let mut serial = run_with_options(&[dir.path().to_path_buf()], &tags, false, false, true, false)?;
+        let mut parallel = run_with_options(&[dir.path().to_path_buf()], &tags, true, false, true, false)?;
+
+        serial.sort_by(|a, b| a.path.cmp(&b.path));
+        parallel.sort_by(|a, b| a.path.cmp(&b.path));
+        assert_eq!(serial, parallel);
+
+        Ok(())
+    }
+}
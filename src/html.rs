@@ -0,0 +1,261 @@
+use crate::cli::Annotation;
+use crate::hunks::GitDiffer;
+use crate::input::{determine_file_type, language_spec, read_file};
+use anyhow::{Context, Result};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+use std::path::PathBuf;
+use syntect::highlighting::ThemeSet;
+use syntect::html::{css_for_theme_with_class_style, ClassStyle, ClassedHTMLGenerator};
+use syntect::parsing::SyntaxSet;
+
+/// Lines of source shown above and below each annotation's own line.
+const CONTEXT_LINES: usize = 3;
+
+const EXTRA_CSS: &str = r#"
+body { font-family: sans-serif; margin: 2rem; background: #1b2b34; color: #c0c5ce; }
+.annotation { margin-bottom: 2rem; }
+.annotation header { font-weight: bold; margin-bottom: 0.5rem; }
+.badge { display: inline-block; padding: 0.1rem 0.5rem; border-radius: 0.25rem; color: white; margin-right: 0.5rem; }
+.badge-todo { background: #c0392b; }
+.badge-note { background: #2980b9; }
+.badge-hypothesis { background: #8e44ad; }
+pre.code { padding: 1rem; overflow-x: auto; }
+.line { display: block; }
+.line.changed { background: rgba(255, 221, 87, 0.15); }
+.line.current { outline: 1px solid #f1c40f; }
+"#;
+
+/// Render `annotations` as a standalone, shareable HTML report.
+///
+/// Each annotation gets a colored tag badge and a few lines of
+/// syntax-highlighted surrounding source, drawn from a `syntect`
+/// `ClassedHTMLGenerator` so the output carries CSS classes rather than
+/// inline styles. A single [`GitDiffer`] is opened once and reused for
+/// every annotation (with per-file results cached) to highlight lines
+/// changed in the working tree relative to the index; the annotation's own
+/// line is outlined.
+pub fn render(annotations: &[Annotation]) -> Result<String> {
+    let syntax_set = SyntaxSet::load_defaults_newlines();
+    let theme_set = ThemeSet::load_defaults();
+    let theme_css =
+        css_for_theme_with_class_style(&theme_set.themes["base16-ocean.dark"], ClassStyle::Spaced)?;
+
+    let discover_from = annotations
+        .first()
+        .and_then(|a| a.path.parent())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("."));
+    let differ = GitDiffer::discover(discover_from).ok();
+    let mut modified_cache: HashMap<PathBuf, HashSet<usize>> = HashMap::new();
+
+    let mut html = String::new();
+    writeln!(html, "<!DOCTYPE html><html><head><meta charset=\"utf-8\">")?;
+    writeln!(html, "<style>{theme_css}{EXTRA_CSS}</style>")?;
+    writeln!(html, "</head><body>")?;
+
+    for annotation in annotations {
+        if let Err(err) = write_annotation(
+            &mut html,
+            annotation,
+            &syntax_set,
+            differ.as_ref(),
+            &mut modified_cache,
+        ) {
+            // Skip files we can no longer read (e.g. deleted since the scan)
+            // rather than failing the whole report.
+            writeln!(
+                html,
+                "<!-- skipped {}: {} -->",
+                escape_html(&annotation.path.display().to_string()),
+                escape_html(&err.to_string())
+            )?;
+        }
+    }
+
+    writeln!(html, "</body></html>")?;
+    Ok(html)
+}
+
+fn write_annotation(
+    html: &mut String,
+    annotation: &Annotation,
+    syntax_set: &SyntaxSet,
+    differ: Option<&GitDiffer>,
+    modified_cache: &mut HashMap<PathBuf, HashSet<usize>>,
+) -> Result<()> {
+    let extension = determine_file_type(&annotation.path)?;
+    let spec = language_spec(&extension).context("Language registry entry vanished")?;
+    let source = read_file(&annotation.path)?;
+    let lines: Vec<&str> = source.lines().collect();
+
+    // The file may have shrunk since it was scanned (e.g. re-read while
+    // rendering); trust the current line count over the stale annotation
+    // rather than letting `lines[start..end]` panic on an out-of-range
+    // slice.
+    anyhow::ensure!(
+        annotation.line >= 1 && annotation.line <= lines.len(),
+        "{} has {} lines now, but the annotation is on line {}",
+        annotation.path.display(),
+        lines.len(),
+        annotation.line
+    );
+
+    let start = annotation.line.saturating_sub(1 + CONTEXT_LINES);
+    let end = (annotation.line + CONTEXT_LINES).min(lines.len());
+    let modified = modified_cache.entry(annotation.path.clone()).or_insert_with(|| {
+        differ
+            .map(|d| d.modified_line_numbers(&annotation.path, None).unwrap_or_default())
+            .unwrap_or_default()
+    });
+
+    let syntax = syntax_set
+        .find_syntax_by_name(&spec.syntect_syntax)
+        .unwrap_or_else(|| syntax_set.find_syntax_plain_text());
+
+    let attribution = annotation
+        .blame
+        .as_ref()
+        .map(|b| format!(" &mdash; {}, {}d old", escape_html(&b.author), b.age_days()))
+        .unwrap_or_default();
+
+    writeln!(
+        html,
+        "<section class=\"annotation\"><header><span class=\"badge badge-{tag}\">{badge_label}</span>{path}:{line}{attribution}</header><pre class=\"code\">",
+        tag = escape_html(&annotation.tag.to_lowercase()),
+        badge_label = escape_html(&annotation.tag),
+        path = escape_html(&annotation.path.display().to_string()),
+        line = annotation.line,
+    )?;
+
+    for (offset, line) in lines[start..end].iter().enumerate() {
+        let line_number = start + offset + 1;
+
+        // Highlighted line-by-line rather than fed through a single
+        // generator for the whole file: constructs spanning multiple lines
+        // (e.g. block comments) may be colored slightly off, which is an
+        // acceptable trade-off for a short context snippet.
+        let mut generator =
+            ClassedHTMLGenerator::new_with_class_style(syntax, syntax_set, ClassStyle::Spaced);
+        generator.parse_html_for_line_which_includes_newline(&format!("{line}\n"))?;
+        let highlighted = generator.finalize();
+
+        let mut classes = "line".to_string();
+        if modified.contains(&line_number) {
+            classes.push_str(" changed");
+        }
+        if line_number == annotation.line {
+            classes.push_str(" current");
+        }
+
+        write!(
+            html,
+            "<span class=\"{classes}\" data-line=\"{line_number}\">{highlighted}</span>"
+        )?;
+    }
+
+    writeln!(html, "</pre></section>")?;
+    Ok(())
+}
+
+/// Escape text for safe interpolation into HTML element content or
+/// attribute values. Only the `syntect`-generated spans, which are already
+/// HTML, bypass this.
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_render_includes_badge_and_highlighted_context() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("example.rs");
+        std::fs::write(&path, "fn main() {\n    // TODO: fix this\n}\n")?;
+
+        let annotations = vec![Annotation {
+            path: path.clone(),
+            line: 2,
+            tag: "TODO".to_string(),
+            text: "fix this".to_string(),
+            blame: None,
+        }];
+
+        let report = render(&annotations)?;
+
+        assert!(report.contains("badge-todo"));
+        assert!(report.contains(&format!("{}:2", path.display())));
+        assert!(report.contains("data-line=\"2\""));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_escapes_path_and_tag() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("<script>evil.rs");
+        std::fs::write(&path, "fn main() {\n    // TODO: fix this\n}\n")?;
+
+        let annotations = vec![Annotation {
+            path: path.clone(),
+            line: 2,
+            tag: "<b>TODO</b>".to_string(),
+            text: "fix this".to_string(),
+            blame: None,
+        }];
+
+        let report = render(&annotations)?;
+
+        assert!(!report.contains("<script>evil.rs"));
+        assert!(report.contains("&lt;script&gt;evil.rs"));
+        assert!(!report.contains("<b>TODO</b>"));
+        assert!(report.contains("&lt;b&gt;TODO&lt;/b&gt;"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_skips_unreadable_file() -> Result<()> {
+        let annotations = vec![Annotation {
+            path: PathBuf::from("/nonexistent/file.rs"),
+            line: 1,
+            tag: "NOTE".to_string(),
+            text: "gone".to_string(),
+            blame: None,
+        }];
+
+        let report = render(&annotations)?;
+        assert!(report.contains("skipped"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_skips_annotation_past_end_of_shrunk_file() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("example.rs");
+        // The annotation claims line 21, but the file on disk now has just
+        // one line, as if it shrank between the scan and the render.
+        std::fs::write(&path, "fn main() {}\n")?;
+
+        let annotations = vec![Annotation {
+            path: path.clone(),
+            line: 21,
+            tag: "TODO".to_string(),
+            text: "fix this".to_string(),
+            blame: None,
+        }];
+
+        let report = render(&annotations)?;
+        assert!(report.contains("skipped"));
+
+        Ok(())
+    }
+}
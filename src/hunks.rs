@@ -1,52 +1,139 @@
-use regex::Regex;
+use anyhow::{Context, Result};
+use git2::{Diff, DiffOptions, Repository};
 use std::collections::HashSet;
-use std::path::Path;
-use std::process::Command;
+use std::path::{Component, Path, PathBuf};
 
-/// Get modified or added lines from git diff.
+/// An opened git repository, kept around so repeated lookups (diff, blame)
+/// don't each pay the cost of rediscovering and opening `.git`.
+pub struct GitDiffer {
+    pub(crate) repo: Repository,
+}
+
+impl GitDiffer {
+    /// Discover and open the repository containing `path`.
+    pub fn discover(path: impl AsRef<Path>) -> Result<Self> {
+        let repo = Repository::discover(path).context("Failed to open git repository")?;
+        Ok(Self { repo })
+    }
+
+    /// Get modified or added lines in `file_path` relative to `base`.
+    ///
+    /// `base` is a revspec such as `"HEAD"`, `"main"`, or a commit SHA. When
+    /// `None`, the working tree is diffed against the index, matching the
+    /// previous `git diff --unified=0` behaviour.
+    ///
+    /// # Returns
+    /// Set of line numbers that were added or modified on the new side of
+    /// the diff. Pure deletions contribute no lines.
+    pub fn modified_line_numbers(
+        &self,
+        file_path: impl AsRef<Path>,
+        base: Option<&str>,
+    ) -> Result<HashSet<usize>> {
+        let workdir = self
+            .repo
+            .workdir()
+            .context("Repository has no working directory")?;
+        // Normalize both sides before stripping: callers commonly pass
+        // paths like "./a.rs" or "./alacritty/a.rs" (exactly what
+        // `scan_directory(".")` produces), and a literal "./" prefix makes
+        // `strip_prefix` fail to match even though the path is really under
+        // `workdir`.
+        let file_path = normalize_path(file_path.as_ref());
+        let workdir = normalize_path(workdir);
+        let relative_path = file_path.strip_prefix(&workdir).unwrap_or(&file_path);
+
+        let mut opts = DiffOptions::new();
+        opts.pathspec(relative_path).context_lines(0);
+
+        let diff = match base {
+            Some(rev) => {
+                let tree = self
+                    .repo
+                    .revparse_single(rev)
+                    .with_context(|| format!("Failed to resolve revision {rev:?}"))?
+                    .peel_to_tree()
+                    .with_context(|| format!("Revision {rev:?} does not point to a tree"))?;
+                self.repo
+                    .diff_tree_to_workdir_with_index(Some(&tree), Some(&mut opts))
+            }
+            None => self.repo.diff_index_to_workdir(None, Some(&mut opts)),
+        }
+        .context("Failed to compute diff")?;
+
+        Ok(collect_added_lines(&diff))
+    }
+}
+
+/// Resolve `path` to an absolute, lexically normalized form, so it can be
+/// reliably compared against (or stripped of) another absolute path like a
+/// repository's workdir regardless of a literal "./" prefix or relative
+/// input. Prefers [`Path::canonicalize`] (which also resolves symlinks);
+/// falls back to joining with the current directory and stripping "."/".."
+/// components lexically when the path doesn't exist on disk (e.g. a file
+/// already deleted from the working tree).
+pub(crate) fn normalize_path(path: &Path) -> PathBuf {
+    if let Ok(canonical) = path.canonicalize() {
+        return canonical;
+    }
+
+    let absolute = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir()
+            .unwrap_or_default()
+            .join(path)
+    };
+
+    let mut normalized = PathBuf::new();
+    for component in absolute.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other.as_os_str()),
+        }
+    }
+    normalized
+}
+
+fn collect_added_lines(diff: &Diff) -> HashSet<usize> {
+    let mut lines = HashSet::new();
+    let _ = diff.foreach(
+        &mut |_delta, _progress| true,
+        None,
+        Some(&mut |_delta, hunk| {
+            let start = hunk.new_start() as usize;
+            let count = hunk.new_lines() as usize;
+            lines.extend(start..start + count);
+            true
+        }),
+        None,
+    );
+    lines
+}
+
+/// Get modified or added lines from git, diffing the working tree against
+/// the index.
 ///
-/// # Arguments
-/// * `file_path`: Path to file to find modified lines.
+/// This is a convenience wrapper around [`GitDiffer`] for callers that just
+/// want the working-tree diff for a single file and don't need to scope
+/// annotations to an arbitrary base revision.
 ///
 /// # Returns
-/// Set of line numbers that were added or modified. If there's an error getting the diff from git,
-/// returns an empty set.
+/// Set of line numbers that were added or modified. If there's an error
+/// opening the repository or computing the diff, returns an empty set.
 pub fn get_modified_line_numbers(file_path: impl AsRef<Path>) -> HashSet<usize> {
     let file_path = file_path.as_ref();
-    // Fall back to "." if there's no parent
     let parent_dir = file_path.parent().unwrap_or_else(|| Path::new("."));
 
-    // Try running the git command in the parent directory
-    let output = match Command::new("git")
-        .current_dir(parent_dir)
-        .args(["diff", "--unified=0"])
-        .arg(file_path)
-        .output()
-    {
-        Ok(out) if out.status.success() => out,
-        _ => return HashSet::new(),
-    };
-
-    let diff_output = match String::from_utf8(output.stdout) {
-        Ok(diff) => diff,
-        Err(_) => return HashSet::new(),
-    };
-
-    // Regex capturing: @@ -<old> +<start>(,<count>)? @@
-    // Group 1 = start, Group 2 = count (optional).
-    let re = Regex::new(r"@@ -\d+(?:,\d+)? \+(\d+)(?:,(\d+))? @@").unwrap();
-
-    re.captures_iter(&diff_output)
-        .filter_map(|caps| {
-            let start: usize = caps.get(1)?.as_str().parse().ok()?;
-            let count: usize = caps
-                .get(2)
-                .and_then(|m| m.as_str().parse().ok())
-                .unwrap_or(1);
-            Some((start, count))
-        })
-        .flat_map(|(start, count)| start..(start + count))
-        .collect()
+    match GitDiffer::discover(parent_dir) {
+        Ok(differ) => differ
+            .modified_line_numbers(file_path, None)
+            .unwrap_or_default(),
+        Err(_) => HashSet::new(),
+    }
 }
 
 #[cfg(test)]
@@ -54,6 +141,7 @@ mod tests {
     use super::*;
     use std::fs::File;
     use std::io::Write;
+    use std::process::Command;
     use tempfile::tempdir;
 
     #[test]
@@ -105,4 +193,82 @@ mod tests {
 
         Ok(())
     }
+
+    /// Run a git subcommand in `dir`, failing the test with its stderr if
+    /// it didn't exit successfully (`Command::output`'s `?` alone only
+    /// surfaces a spawn error, not a nonzero exit status).
+    fn git(dir: &Path, args: &[&str]) -> Result<(), Box<dyn std::error::Error>> {
+        let output = Command::new("git").args(args).current_dir(dir).output()?;
+        if !output.status.success() {
+            return Err(format!(
+                "git {args:?} failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            )
+            .into());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_modified_lines_against_explicit_base() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+
+        git(dir.path(), &["init"])?;
+        git(dir.path(), &["config", "user.email", "test@example.com"])?;
+        git(dir.path(), &["config", "user.name", "Test User"])?;
+
+        let file_path = dir.path().join("test.txt");
+        let mut file = File::create(&file_path)?;
+        writeln!(file, "line 1\nline 2")?;
+
+        git(dir.path(), &["add", "test.txt"])?;
+        git(dir.path(), &["commit", "-m", "initial"])?;
+
+        // Commit the change too, so the working tree/index diff is empty and
+        // only an explicit base revision reveals it.
+        let mut file = File::create(&file_path)?;
+        writeln!(file, "line 1\nmodified line 2\nline 3")?;
+        git(dir.path(), &["commit", "-am", "modify"])?;
+
+        let differ = GitDiffer::discover(&dir)?;
+
+        assert!(differ
+            .modified_line_numbers(&file_path, None)?
+            .is_empty());
+
+        let against_parent = differ.modified_line_numbers(&file_path, Some("HEAD~1"))?;
+        assert!(against_parent.contains(&2));
+        assert!(against_parent.contains(&3));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_modified_lines_with_dot_slash_prefix() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+
+        git(dir.path(), &["init"])?;
+        git(dir.path(), &["config", "user.email", "test@example.com"])?;
+        git(dir.path(), &["config", "user.name", "Test User"])?;
+
+        let file_path = dir.path().join("test.txt");
+        let mut file = File::create(&file_path)?;
+        writeln!(file, "line 1\nline 2\nline 3")?;
+
+        git(dir.path(), &["add", "test.txt"])?;
+        git(dir.path(), &["commit", "-m", "initial"])?;
+
+        let mut file = File::create(&file_path)?;
+        writeln!(file, "line 1\nmodified line 2\nline 3")?;
+
+        let differ = GitDiffer::discover(&dir)?;
+
+        // A literal "./" prefix, exactly what `scan_directory(".")` yields,
+        // shouldn't stop the path from matching the workdir prefix.
+        let dotted_path = PathBuf::from(format!("{}/./test.txt", dir.path().display()));
+        let modified_lines = differ.modified_line_numbers(&dotted_path, None)?;
+        assert!(modified_lines.contains(&2));
+
+        Ok(())
+    }
 }
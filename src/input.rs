@@ -1,91 +1,205 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use ignore::WalkBuilder;
+use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
-use std::sync::LazyLock;
-use walkdir::WalkDir;
-
-#[derive(Debug, Clone, PartialEq)]
-pub enum FileType {
-    Python,
-    Rust,
-    JavaScript,
+use std::sync::{Arc, LazyLock, Mutex, RwLock};
+
+/// How to extract and display comments for one language.
+///
+/// Registered under a file extension (without the leading dot) in the
+/// language registry; see [`register_language`] and [`load_config`].
+#[derive(Clone)]
+pub struct LanguageSpec {
+    pub language: tree_sitter::Language,
+    pub comment_query: String,
+    pub syntect_syntax: String,
 }
 
-impl TryFrom<&PathBuf> for FileType {
-    type Error = anyhow::Error;
-    fn try_from(path: &PathBuf) -> Result<Self, Self::Error> {
-        match path.extension().and_then(|ext| ext.to_str()) {
-            Some("py") => Ok(FileType::Python),
-            Some("rs") => Ok(FileType::Rust),
-            Some("js") => Ok(FileType::JavaScript),
-            _ => Err(anyhow::anyhow!("Invalid file extension: {:?}.", path)),
-        }
-    }
+static REGISTRY: LazyLock<RwLock<HashMap<String, LanguageSpec>>> =
+    LazyLock::new(|| RwLock::new(default_languages()));
+
+static QUERY_CACHE: LazyLock<Mutex<HashMap<String, Arc<tree_sitter::Query>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+fn default_languages() -> HashMap<String, LanguageSpec> {
+    HashMap::from([
+        (
+            "py".to_string(),
+            LanguageSpec {
+                language: tree_sitter_python::LANGUAGE.into(),
+                comment_query: "(comment) @comment".to_string(),
+                syntect_syntax: "Python".to_string(),
+            },
+        ),
+        (
+            "rs".to_string(),
+            LanguageSpec {
+                language: tree_sitter_rust::LANGUAGE.into(),
+                comment_query: "(line_comment) @comment\n(block_comment) @comment".to_string(),
+                syntect_syntax: "Rust".to_string(),
+            },
+        ),
+        (
+            "js".to_string(),
+            LanguageSpec {
+                language: tree_sitter_javascript::LANGUAGE.into(),
+                comment_query: "(comment) @comment".to_string(),
+                syntect_syntax: "JavaScript".to_string(),
+            },
+        ),
+    ])
+}
+
+/// Register a language (or override an already-registered one) under
+/// `extension` (without the leading dot), so matching files are picked up
+/// by [`scan_directory`] and parsed with `spec`'s tree-sitter grammar.
+///
+/// `tree_sitter::Language` values come from a compiled grammar crate, so
+/// adding a brand-new language still needs that crate as a dependency and a
+/// call to this function at startup; [`load_config`] only lets a config
+/// file customize or alias languages that are already registered.
+pub fn register_language(extension: impl Into<String>, spec: LanguageSpec) {
+    let extension = extension.into();
+    QUERY_CACHE.lock().unwrap().remove(&extension);
+    REGISTRY.write().unwrap().insert(extension, spec);
+}
+
+/// A config-file entry for one extension. `like` names an already
+/// registered extension (e.g. `"js"`) to inherit the tree-sitter grammar
+/// from; `comment_query` and `syntect_syntax` may override that language's
+/// defaults.
+#[derive(serde::Deserialize)]
+struct ConfigLanguage {
+    like: String,
+    comment_query: Option<String>,
+    syntect_syntax: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+struct Config {
+    #[serde(default)]
+    languages: HashMap<String, ConfigLanguage>,
 }
 
-static TS_QUERY_PYTHON: LazyLock<tree_sitter::Query> = LazyLock::new(|| {
-    tree_sitter::Query::new(&tree_sitter_python::LANGUAGE.into(), "(comment) @comment")
-        .expect("Query must be valid")
-});
-
-static TS_QUERY_RUST: LazyLock<tree_sitter::Query> = LazyLock::new(|| {
-    tree_sitter::Query::new(
-        &tree_sitter_rust::LANGUAGE.into(),
-        "(line_comment) @comment
-(block_comment) @comment",
-    )
-    .expect("Query must be valid")
-});
-
-static TS_QUERY_JAVASCRIPT: LazyLock<tree_sitter::Query> = LazyLock::new(|| {
-    tree_sitter::Query::new(
-        &tree_sitter_javascript::LANGUAGE.into(),
-        "(comment) @comment",
-    )
-    .expect("Query must be valid")
-});
-
-impl FileType {
-    pub fn tree_sitter_query(&self) -> &'static tree_sitter::Query {
-        match self {
-            FileType::Python => &TS_QUERY_PYTHON,
-            FileType::Rust => &TS_QUERY_RUST,
-            FileType::JavaScript => &TS_QUERY_JAVASCRIPT,
-        }
+/// Load extension registrations from a TOML config file and merge them
+/// into the registry, e.g.:
+///
+/// ```toml
+/// [languages.jsx]
+/// like = "js"
+///
+/// [languages.mjs]
+/// like = "js"
+/// comment_query = "(comment) @comment"
+/// ```
+pub fn load_config(path: impl AsRef<std::path::Path>) -> Result<()> {
+    let contents = fs::read_to_string(path.as_ref())
+        .with_context(|| format!("Failed to read language config {:?}", path.as_ref()))?;
+    let config: Config = toml::from_str(&contents).context("Failed to parse language config")?;
+
+    for (extension, entry) in config.languages {
+        let base = language_spec(&entry.like).with_context(|| {
+            format!(
+                "Extension {extension:?} wants to inherit from {:?}, but it isn't registered \
+                 (register its grammar with register_language first)",
+                entry.like
+            )
+        })?;
+        register_language(
+            extension,
+            LanguageSpec {
+                language: base.language,
+                comment_query: entry.comment_query.unwrap_or(base.comment_query),
+                syntect_syntax: entry.syntect_syntax.unwrap_or(base.syntect_syntax),
+            },
+        );
     }
+    Ok(())
+}
+
+/// Look up the registered [`LanguageSpec`] for `extension` (without the
+/// leading dot).
+pub fn language_spec(extension: &str) -> Option<LanguageSpec> {
+    REGISTRY.read().unwrap().get(extension).cloned()
+}
 
-    pub fn tree_sitter_language(&self) -> tree_sitter::Language {
-        match self {
-            FileType::Python => tree_sitter_python::LANGUAGE.into(),
-            FileType::Rust => tree_sitter_rust::LANGUAGE.into(),
-            FileType::JavaScript => tree_sitter_javascript::LANGUAGE.into(),
-        }
+/// The compiled tree-sitter query that extracts comments for `extension`,
+/// compiling and caching it on first use.
+pub fn tree_sitter_query(extension: &str) -> Option<Arc<tree_sitter::Query>> {
+    if let Some(query) = QUERY_CACHE.lock().unwrap().get(extension) {
+        return Some(query.clone());
     }
+    let spec = language_spec(extension)?;
+    let query = Arc::new(
+        tree_sitter::Query::new(&spec.language, &spec.comment_query).expect("Query must be valid"),
+    );
+    QUERY_CACHE
+        .lock()
+        .unwrap()
+        .insert(extension.to_string(), query.clone());
+    Some(query)
 }
 
 pub fn read_file(path: &PathBuf) -> Result<String> {
     fs::read_to_string(path).map_err(|e| anyhow::anyhow!("Failed to read file: {}", e))
 }
 
-pub fn determine_file_type(path: &PathBuf) -> Result<FileType> {
-    FileType::try_from(path)
+/// The registered extension (without the leading dot) for `path`, e.g.
+/// `"rs"`. Returns an error if `path` has no extension or none is
+/// registered for it.
+pub fn determine_file_type(path: &PathBuf) -> Result<String> {
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .ok_or_else(|| anyhow::anyhow!("Invalid file extension: {:?}.", path))?;
+    if REGISTRY.read().unwrap().contains_key(extension) {
+        Ok(extension.to_string())
+    } else {
+        Err(anyhow::anyhow!("Invalid file extension: {:?}.", path))
+    }
 }
 
+/// Scan `path` for files with a recognized extension (see
+/// [`determine_file_type`]).
+///
+/// Honors `.gitignore`, `.ignore`, and other standard ignore rules, so
+/// vendored and build-output directories like `target/` or `node_modules/`
+/// are pruned instead of visited.
 pub fn scan_directory(path: &PathBuf) -> Result<Vec<PathBuf>> {
-    let mut files = Vec::new();
-    for entry in WalkDir::new(path)
+    scan_directory_with_options(path, true)
+}
+
+/// Like [`scan_directory`], but lets callers disable ignore-file handling
+/// with `respect_ignore: false` to walk every file regardless of
+/// `.gitignore`/`.ignore` rules.
+pub fn scan_directory_with_options(path: &PathBuf, respect_ignore: bool) -> Result<Vec<PathBuf>> {
+    let files = Mutex::new(Vec::new());
+
+    let mut builder = WalkBuilder::new(path);
+    builder
         .follow_links(true)
-        .into_iter()
-        .filter_map(|e| e.ok())
-    {
-        if entry.file_type().is_file() {
-            let path = entry.path().to_path_buf();
-            if determine_file_type(&path).is_ok() {
-                files.push(path);
+        .standard_filters(respect_ignore)
+        // Honor a bare `.gitignore` even outside an actual git repository;
+        // `ignore`'s default (`require_git(true)`) would otherwise silently
+        // skip it.
+        .require_git(false);
+
+    builder.build_parallel().run(|| {
+        Box::new(|entry| {
+            if let Ok(entry) = entry {
+                if entry.file_type().map(|ft| ft.is_file()).unwrap_or(false) {
+                    let candidate = entry.path().to_path_buf();
+                    if determine_file_type(&candidate).is_ok() {
+                        files.lock().unwrap().push(candidate);
+                    }
+                }
             }
-        }
-    }
-    Ok(files)
+            ignore::WalkState::Continue
+        })
+    });
+
+    Ok(files.into_inner().unwrap())
 }
 
 #[cfg(test)]
@@ -94,18 +208,43 @@ mod tests {
 
     #[test]
     fn test_file_type_detection() {
-        assert_eq!(
-            determine_file_type(&PathBuf::from("test.py")).unwrap(),
-            FileType::Python
-        );
-        assert_eq!(
-            determine_file_type(&PathBuf::from("test.rs")).unwrap(),
-            FileType::Rust
-        );
-        assert_eq!(
-            determine_file_type(&PathBuf::from("test.js")).unwrap(),
-            FileType::JavaScript
-        );
+        assert_eq!(determine_file_type(&PathBuf::from("test.py")).unwrap(), "py");
+        assert_eq!(determine_file_type(&PathBuf::from("test.rs")).unwrap(), "rs");
+        assert_eq!(determine_file_type(&PathBuf::from("test.js")).unwrap(), "js");
         assert!(determine_file_type(&PathBuf::from("test.txt")).is_err());
     }
+
+    #[test]
+    fn test_scan_directory_respects_gitignore() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(dir.path().join(".gitignore"), "ignored.py\n")?;
+        fs::write(dir.path().join("ignored.py"), "# ignored")?;
+        fs::write(dir.path().join("kept.py"), "# kept")?;
+
+        let found = scan_directory(&dir.path().to_path_buf())?;
+        assert!(found.iter().any(|p| p.ends_with("kept.py")));
+        assert!(!found.iter().any(|p| p.ends_with("ignored.py")));
+
+        let found_unfiltered = scan_directory_with_options(&dir.path().to_path_buf(), false)?;
+        assert!(found_unfiltered.iter().any(|p| p.ends_with("ignored.py")));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_config_aliases_existing_language() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let config_path = dir.path().join("anot.toml");
+        fs::write(
+            &config_path,
+            "[languages.jsx]\nlike = \"js\"\n",
+        )?;
+
+        load_config(&config_path)?;
+
+        assert_eq!(determine_file_type(&PathBuf::from("Component.jsx")).unwrap(), "jsx");
+        assert!(tree_sitter_query("jsx").is_some());
+
+        Ok(())
+    }
 }
@@ -0,0 +1,6 @@
+pub mod blame;
+pub mod cli;
+pub mod html;
+pub mod hunks;
+pub mod input;
+pub mod watch;
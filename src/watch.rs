@@ -0,0 +1,194 @@
+use crate::cli::{attribute_blame, extract_file, filter_to_changed_lines, Annotation};
+use crate::hunks::{normalize_path, GitDiffer};
+use crate::input::scan_directory_with_options;
+use anyhow::Result;
+use notify::{RecursiveMode, Watcher};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::channel;
+use std::time::Duration;
+
+/// Per-path cache of the last-extracted annotations, so a watch iteration
+/// only re-extracts files that actually changed.
+struct WatchCache {
+    annotations: HashMap<PathBuf, Vec<Annotation>>,
+}
+
+/// Run in watch mode: scan `paths` once, print the report, then keep
+/// re-scanning changed files as the user edits, clearing and reprinting the
+/// report after each debounced batch of events.
+///
+/// New files matching a registered extension are picked up as they appear;
+/// deleted files drop out. Editor swap/temp files are ignored. Pass
+/// `respect_ignore: false` to walk every file regardless of
+/// `.gitignore`/`.ignore` rules (see
+/// [`crate::input::scan_directory_with_options`]) — this governs both the
+/// initial scan and every live update, so e.g. edits under a gitignored
+/// `target/` directory stay excluded for the life of the watch session just
+/// like the initial scan, rather than only being filtered once up front.
+/// When `changed_only` or `with_blame` is set, a single [`GitDiffer`] is
+/// opened once for the whole watch session and reused to re-evaluate
+/// modified lines and/or blame attribution for each touched file, so both
+/// views stay accurate as the user edits without rediscovering the
+/// repository on every event.
+pub fn watch(
+    paths: &[PathBuf],
+    tags: &[String],
+    changed_only: bool,
+    with_blame: bool,
+    respect_ignore: bool,
+) -> Result<()> {
+    let mut cache = WatchCache {
+        annotations: HashMap::new(),
+    };
+
+    let differ = if changed_only || with_blame {
+        let discover_from = paths.first().cloned().unwrap_or_else(|| PathBuf::from("."));
+        GitDiffer::discover(discover_from).ok()
+    } else {
+        None
+    };
+
+    for path in paths {
+        for file in scan_directory_with_options(path, respect_ignore)? {
+            if let Ok(found) = extract_file(&file, tags) {
+                cache.annotations.insert(
+                    file,
+                    apply_filter(found, differ.as_ref(), changed_only, with_blame),
+                );
+            }
+        }
+    }
+    print_report(&cache);
+
+    let (tx, rx) = channel();
+    let mut watcher = notify::recommended_watcher(tx)?;
+    for path in paths {
+        watcher.watch(path, RecursiveMode::Recursive)?;
+    }
+
+    while let Ok(event) = rx.recv() {
+        let Ok(event) = event else { continue };
+
+        // Debounce: editors often emit several events per save, so wait a
+        // beat and drain whatever else is already queued from this burst.
+        std::thread::sleep(Duration::from_millis(200));
+        let mut touched = event.paths;
+        while let Ok(Ok(next)) = rx.try_recv() {
+            touched.extend(next.paths);
+        }
+
+        // Re-scanned once per batch (not once per touched file) and reused
+        // below, so a burst of events doesn't re-walk the tree repeatedly.
+        let allowed = allowed_files(paths, respect_ignore);
+
+        for path in touched {
+            if is_temp_or_swap(&path) {
+                continue;
+            }
+            if !path.exists() {
+                cache.annotations.remove(&path);
+                continue;
+            }
+            if let Some(allowed) = &allowed {
+                if !allowed.contains(&normalize_path(&path)) {
+                    cache.annotations.remove(&path);
+                    continue;
+                }
+            }
+            match extract_file(&path, tags) {
+                Ok(found) => {
+                    cache.annotations.insert(
+                        path,
+                        apply_filter(found, differ.as_ref(), changed_only, with_blame),
+                    );
+                }
+                Err(_) => {
+                    cache.annotations.remove(&path);
+                }
+            }
+        }
+
+        print_report(&cache);
+    }
+
+    Ok(())
+}
+
+/// The set of files that currently pass ignore rules under `paths`, used to
+/// re-check a live-touched file the same way the initial scan did. Returns
+/// `None` when `respect_ignore` is false, since then every touched file is
+/// allowed and there's nothing to check.
+fn allowed_files(paths: &[PathBuf], respect_ignore: bool) -> Option<HashSet<PathBuf>> {
+    if !respect_ignore {
+        return None;
+    }
+    let mut files = HashSet::new();
+    for root in paths {
+        if let Ok(found) = scan_directory_with_options(root, true) {
+            files.extend(found.iter().map(|f| normalize_path(f)));
+        }
+    }
+    Some(files)
+}
+
+/// Re-evaluates the changed-lines filter and/or blame attribution for a
+/// freshly extracted file's annotations, depending on which of
+/// `changed_only`/`with_blame` is set; a no-op if neither is.
+fn apply_filter(
+    found: Vec<Annotation>,
+    differ: Option<&GitDiffer>,
+    changed_only: bool,
+    with_blame: bool,
+) -> Vec<Annotation> {
+    let found = if changed_only {
+        filter_to_changed_lines(found, differ)
+    } else {
+        found
+    };
+    if with_blame {
+        attribute_blame(found, differ)
+    } else {
+        found
+    }
+}
+
+/// Whether `path` looks like an editor swap or temp file rather than a
+/// genuine save, e.g. `.foo.swp` or `foo.rs~`.
+fn is_temp_or_swap(path: &Path) -> bool {
+    let is_swap_ext = matches!(
+        path.extension().and_then(|e| e.to_str()),
+        Some("swp") | Some("swo") | Some("tmp")
+    );
+    let is_backup = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .map(|n| n.ends_with('~'))
+        .unwrap_or(false);
+    is_swap_ext || is_backup
+}
+
+fn print_report(cache: &WatchCache) {
+    // Clear the terminal before reprinting the full, up-to-date report.
+    print!("\x1B[2J\x1B[1;1H");
+
+    let mut paths: Vec<&PathBuf> = cache.annotations.keys().collect();
+    paths.sort();
+    for path in paths {
+        for annotation in &cache.annotations[path] {
+            let attribution = annotation
+                .blame
+                .as_ref()
+                .map(|b| format!(" ({}, {}d old)", b.author, b.age_days()))
+                .unwrap_or_default();
+            println!(
+                "{}:{}: [{}] {}{}",
+                annotation.path.display(),
+                annotation.line,
+                annotation.tag,
+                annotation.text,
+                attribution
+            );
+        }
+    }
+}